@@ -10,6 +10,7 @@ use std::env;
 use std::path::{Path, PathBuf};
 
 use cmake::Config;
+use sha2::{Digest, Sha256};
 use walkdir::WalkDir;
 
 #[cfg(not(target_os = "windows"))]
@@ -38,6 +39,188 @@ fn main() {
     add_search_paths("LIBRARY_PATH");
     add_search_paths("CMAKE_LIBRARY_PATH");
 
+    let include_dir = if cfg!(feature = "system") {
+        link_system()
+    } else {
+        if cfg!(feature = "download-prebuilt") {
+            link_libraries(download_prebuilt());
+        } else {
+            build_from_source();
+        }
+        Some(PathBuf::from("CTranslate2/include"))
+    };
+
+    let mut bridge = cxx_build::bridges([
+        "src/sys/types.rs",
+        "src/sys/config.rs",
+        "src/sys/scoring.rs",
+        "src/sys/translator.rs",
+        "src/sys/generator.rs",
+        "src/sys/storage_view.rs",
+        "src/sys/whisper.rs",
+    ]);
+    bridge
+        .file("src/sys/translator.cpp")
+        .file("src/sys/generator.cpp")
+        .file("src/sys/whisper.cpp")
+        .std("c++17")
+        .static_crt(cfg!(target_os = "windows"))
+        .flag_if_supported("/EHsc");
+    if let Some(dir) = include_dir {
+        bridge.include(dir);
+    }
+
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("macos") {
+        if let Some(archs) = macos_osx_architectures() {
+            for arch in archs.split(';') {
+                bridge.flag("-arch").flag(arch);
+            }
+        }
+    }
+
+    bridge.compile("ct2rs");
+}
+
+fn macos_osx_architectures() -> Option<String> {
+    println!("cargo:rerun-if-env-changed=CT2_OSX_ARCHITECTURES");
+    if let Ok(archs) = env::var("CT2_OSX_ARCHITECTURES") {
+        return Some(archs);
+    }
+    if cfg!(feature = "macos-universal") {
+        return Some("arm64;x86_64".to_string());
+    }
+    if env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("aarch64") {
+        return Some("arm64".to_string());
+    }
+    None
+}
+
+fn link_system() -> Option<PathBuf> {
+    match pkg_config::Config::new().probe("ctranslate2") {
+        Ok(library) => library.include_paths.into_iter().next(),
+        Err(err) => {
+            println!("cargo:warning=pkg-config lookup for ctranslate2 failed ({err}); falling back to CT2_LIB_DIR/CT2_INCLUDE_DIR");
+            println!("cargo:rerun-if-env-changed=CT2_LIB_DIR");
+            println!("cargo:rerun-if-env-changed=CT2_INCLUDE_DIR");
+
+            let lib_dir = env::var("CT2_LIB_DIR").expect(
+                "neither pkg-config nor CT2_LIB_DIR/CT2_INCLUDE_DIR found a system CTranslate2 installation",
+            );
+            let include_dir = env::var("CT2_INCLUDE_DIR")
+                .expect("CT2_INCLUDE_DIR must be set alongside CT2_LIB_DIR");
+
+            println!("cargo:rustc-link-search={lib_dir}");
+            println!("cargo:rustc-link-lib=ctranslate2");
+
+            Some(PathBuf::from(include_dir))
+        }
+    }
+}
+
+fn download_prebuilt() -> PathBuf {
+    println!("cargo:rerun-if-env-changed=CT2_PREBUILT_DIR");
+    if let Ok(dir) = env::var("CT2_PREBUILT_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is not set"));
+    let key = prebuilt_platform_key();
+    let version = env::var("CARGO_PKG_VERSION").expect("CARGO_PKG_VERSION is not set");
+    let base_url = env::var("CT2_PREBUILT_BASE_URL").unwrap_or_else(|_| {
+        "https://github.com/frederik-uni/ctranslate2-rs/releases/download".to_string()
+    });
+    println!("cargo:rerun-if-env-changed=CT2_PREBUILT_BASE_URL");
+
+    let archive_name = format!("ctranslate2-{key}.tar.gz");
+    let archive_url = format!("{base_url}/v{version}/{archive_name}");
+
+    let archive_path = out_dir.join(&archive_name);
+    download_file(&archive_url, &archive_path);
+    verify_checksum(&archive_path, prebuilt_checksum(&version, &key));
+
+    let extract_dir = out_dir.join("ctranslate2-prebuilt");
+    std::fs::create_dir_all(&extract_dir).expect("failed to create extraction directory");
+    let status = std::process::Command::new("tar")
+        .args(["xzf"])
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(&extract_dir)
+        .status()
+        .expect("failed to run `tar` to extract the prebuilt archive");
+    assert!(
+        status.success(),
+        "failed to extract {}",
+        archive_path.display()
+    );
+
+    extract_dir
+}
+
+// SHA-256 of each release's prebuilt archive, pinned here rather than
+// fetched alongside the archive: a checksum served from the same
+// (potentially tampered or untrusted) host/mirror as the archive it's
+// supposed to verify catches transfer corruption only, not tampering. Add a
+// row for every (version, target) pair before publishing that release's
+// prebuilt archives.
+const PREBUILT_CHECKSUMS: &[(&str, &str, &str)] = &[];
+
+fn prebuilt_checksum(version: &str, key: &str) -> &'static str {
+    PREBUILT_CHECKSUMS
+        .iter()
+        .find(|(v, k, _)| *v == version && *k == key)
+        .map(|(_, _, sha256)| *sha256)
+        .unwrap_or_else(|| {
+            panic!(
+                "no pinned checksum for ctranslate2-{key}.tar.gz at version {version}; add an \
+                 entry to PREBUILT_CHECKSUMS in build.rs before publishing this release's \
+                 prebuilt archives"
+            )
+        })
+}
+
+fn prebuilt_platform_key() -> String {
+    let os = env::var("CARGO_CFG_TARGET_OS").expect("CARGO_CFG_TARGET_OS is not set");
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").expect("CARGO_CFG_TARGET_ARCH is not set");
+    if os == "windows" {
+        let env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+        format!("{os}-{arch}-{env}")
+    } else {
+        format!("{os}-{arch}")
+    }
+}
+
+fn download_file(url: &str, dest: &Path) {
+    let status = std::process::Command::new("curl")
+        .args([
+            "--fail",
+            "--location",
+            "--silent",
+            "--show-error",
+            "--output",
+        ])
+        .arg(dest)
+        .arg(url)
+        .status()
+        .expect("failed to run `curl` to download the prebuilt archive");
+    assert!(status.success(), "failed to download {url}");
+}
+
+fn verify_checksum(archive_path: &Path, expected: &str) {
+    let contents =
+        std::fs::read(archive_path).expect("failed to read the downloaded prebuilt archive");
+    let actual = format!("{:x}", Sha256::digest(&contents));
+
+    assert_eq!(
+        expected,
+        actual,
+        "checksum mismatch for {}: expected {expected}, got {actual}",
+        archive_path.display()
+    );
+}
+
+fn build_from_source() {
+    ensure_submodules_initialized();
+
     let mut cmake = Config::new("CTranslate2");
     cmake
         .define("BUILD_CLI", "OFF")
@@ -79,9 +262,16 @@ fn main() {
                 println!("cargo:rustc-link-lib=static=culibos");
             }
         }
+
+        println!("cargo:rerun-if-env-changed=CT2_CUDA_ARCH");
+        if let Some(arch_list) = cuda_arch_list() {
+            cmake.define("CUDA_ARCH_LIST", arch_list);
+        }
     }
-    if cfg!(target_os = "macos") && cfg!(target_arch = "aarch64") {
-        cmake.define("CMAKE_OSX_ARCHITECTURES", "arm64");
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("macos") {
+        if let Some(archs) = macos_osx_architectures() {
+            cmake.define("CMAKE_OSX_ARCHITECTURES", archs);
+        }
     }
 
     if cfg!(feature = "mkl") {
@@ -113,24 +303,46 @@ fn main() {
 
     let ctranslate2 = cmake.build();
     link_libraries(ctranslate2.join("build"));
+}
 
-    cxx_build::bridges([
-        "src/sys/types.rs",
-        "src/sys/config.rs",
-        "src/sys/scoring.rs",
-        "src/sys/translator.rs",
-        "src/sys/generator.rs",
-        "src/sys/storage_view.rs",
-        "src/sys/whisper.rs",
-    ])
-    .file("src/sys/translator.cpp")
-    .file("src/sys/generator.cpp")
-    .file("src/sys/whisper.cpp")
-    .include("CTranslate2/include")
-    .std("c++17")
-    .static_crt(cfg!(target_os = "windows"))
-    .flag_if_supported("/EHsc")
-    .compile("ct2rs");
+fn ensure_submodules_initialized() {
+    println!("cargo:rerun-if-env-changed=CT2_AUTO_SUBMODULE");
+
+    let ctranslate2_dir = Path::new("CTranslate2");
+    let submodule_paths = [
+        ctranslate2_dir.to_path_buf(),
+        ctranslate2_dir.join("third_party/cutlass"),
+        ctranslate2_dir.join("third_party/eigen"),
+        ctranslate2_dir.join("third_party/spdlog"),
+    ];
+
+    if submodule_paths.iter().any(|path| is_dir_empty(path))
+        && env::var_os("CT2_AUTO_SUBMODULE").is_some()
+    {
+        let status = std::process::Command::new("git")
+            .args(["submodule", "update", "--init", "--recursive"])
+            .status()
+            .expect("failed to run `git submodule update --init --recursive`");
+        assert!(
+            status.success(),
+            "`git submodule update --init --recursive` failed"
+        );
+    }
+
+    if submodule_paths.iter().any(|path| is_dir_empty(path)) {
+        panic!(
+            "CTranslate2 submodules are not initialized (missing files under {}). \
+             Run `git submodule update --init --recursive`, or set CT2_AUTO_SUBMODULE=1 \
+             to have the build do it automatically.",
+            ctranslate2_dir.display()
+        );
+    }
+}
+
+fn is_dir_empty(path: &Path) -> bool {
+    std::fs::read_dir(path)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(true)
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -215,3 +427,31 @@ fn cuda_root() -> Option<PathBuf> {
         .map(Into::<PathBuf>::into)
         .find(|path| path.join("include").join("cuda.h").is_file())
 }
+
+fn cuda_arch_list() -> Option<String> {
+    if let Ok(arch) = env::var("CT2_CUDA_ARCH") {
+        return Some(arch);
+    }
+
+    let output = std::process::Command::new("nvidia-smi")
+        .args(["--query-gpu=compute_cap", "--format=csv,noheader"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let mut caps: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    caps.sort();
+    caps.dedup();
+
+    if caps.is_empty() {
+        None
+    } else {
+        Some(caps.join(";"))
+    }
+}